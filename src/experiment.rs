@@ -1,27 +1,41 @@
-use metrics::counter;
 use std::fmt::Display;
 use std::future::Future;
 use std::marker::PhantomData;
+use std::time::{Duration, Instant};
+
 use tracing::{info_span, instrument::Instrumented, Instrument};
 
-use crate::mismatch::{self, Mismatch, MismatchHandler};
+use crate::instrumentation::{Instrumentation, MetricsTracing};
+use crate::mismatch::{self, Ignore, Mismatch, MismatchHandler};
+use crate::normalize::{Equality, FnComparison, FnNormalizer, Normalizer};
+use crate::ordering::Ordering;
 use crate::rollout::{RolloutDecision, RolloutStrategy};
 
 /// An individual experiment. See crate-level documentation for an example on how
 /// to use
-pub struct Experiment<T, C, E, R, M> {
+///
+/// `T` is the type produced by the control method, which is also the type the
+/// experiment returns. The experimental method may produce a different type;
+/// see [`Experiment::normalize`].
+pub struct Experiment<T, C, E, R, M, I, N, G> {
     result_type: PhantomData<T>,
     control_builder: C,
     experimental_builder: E,
     rollout_strategy: R,
     mismatch_handler: M,
+    instrumentation: I,
+    normalizer: N,
+    ignore: G,
+    ordering: Ordering,
     name: &'static str,
 }
 
-impl<T> Experiment<T, (), (), (), mismatch::AlwaysControl> {
-    /// Create a new experiment. The only provided default is accepting the
-    /// control value in the mismatch handler. All other builder-style functions
-    /// must be called before `run` can be called.
+impl<T> Experiment<T, (), (), (), mismatch::AlwaysControl, MetricsTracing, Equality, mismatch::NeverIgnore> {
+    /// Create a new experiment. The provided defaults are accepting the control
+    /// value in the mismatch handler, comparing values with `PartialEq`, never
+    /// ignoring a mismatch, and reporting telemetry through [`MetricsTracing`].
+    /// All other builder-style functions must be called before `run` can be
+    /// called.
     pub fn new(name: &'static str) -> Self {
         Self {
             name,
@@ -30,6 +44,10 @@ impl<T> Experiment<T, (), (), (), mismatch::AlwaysControl> {
             experimental_builder: (),
             mismatch_handler: mismatch::AlwaysControl,
             rollout_strategy: (),
+            instrumentation: MetricsTracing,
+            normalizer: Equality,
+            ignore: mismatch::NeverIgnore,
+            ordering: Ordering::ControlFirst,
         }
     }
 }
@@ -51,102 +69,329 @@ where
     ))
 }
 
-impl<T, C, E, R, M> Experiment<T, C, E, R, M> {
+/// Await `future`, returning its output alongside the wall-clock time it took
+/// to resolve.
+async fn timed<F, T>(future: F) -> (T, Duration)
+where
+    F: Future<Output = T>,
+{
+    let start = Instant::now();
+    let output = future.await;
+    // `Instant::elapsed` is monotonic, so this can't go backwards.
+    (output, start.elapsed())
+}
+
+/// Report a variant's latency through the instrumentation. Whether it is
+/// recorded into the latency histograms is up to the [`Instrumentation`], so
+/// `NoInstrumentation` pays nothing.
+fn report_timing<I>(instrumentation: &I, name: &'static str, kind: &'static str, elapsed: Duration)
+where
+    I: Instrumentation,
+{
+    instrumentation.on_timing(name, kind, elapsed);
+}
+
+/// Report the speedup of the experimental arm relative to the control, skipping
+/// the degenerate case where the experimental arm recorded no measurable time.
+fn report_speedup<I>(instrumentation: &I, name: &'static str, control: Duration, experimental: Duration)
+where
+    I: Instrumentation,
+{
+    if experimental.is_zero() {
+        return;
+    }
+
+    instrumentation.on_speedup(name, control.as_secs_f64() / experimental.as_secs_f64());
+}
+
+/// Poll the control and experimental arms according to the chosen [`Ordering`],
+/// timing each, and return their `(value, elapsed)` pairs in control-then-
+/// experimental order regardless of which was polled first.
+async fn poll_pair<CF, EF, TC, TE>(
+    ordering: Ordering,
+    control: CF,
+    experimental: EF,
+) -> ((TC, Duration), (TE, Duration))
+where
+    CF: Future<Output = TC>,
+    EF: Future<Output = TE>,
+{
+    let experimental_first = match ordering {
+        Ordering::ExperimentalFirst => true,
+        Ordering::ControlFirst => false,
+        // Both sequential and concurrent-random pick the first-polled arm at
+        // random so neither gets a systematic head start.
+        Ordering::Random | Ordering::Sequential => rand::random(),
+    };
+
+    if let Ordering::Sequential = ordering {
+        return if experimental_first {
+            let experimental = timed(experimental).await;
+            let control = timed(control).await;
+            (control, experimental)
+        } else {
+            let control = timed(control).await;
+            let experimental = timed(experimental).await;
+            (control, experimental)
+        };
+    }
+
+    if experimental_first {
+        let (experimental, control) = tokio::join!(timed(experimental), timed(control));
+        (control, experimental)
+    } else {
+        tokio::join!(timed(control), timed(experimental))
+    }
+}
+
+impl<T, C, E, R, M, I, N, G> Experiment<T, C, E, R, M, I, N, G> {
     /// Use the future given here as the control, or the existing method for
     /// calculating a value
-    pub fn control<NC>(self, control_builder: NC) -> Experiment<T, NC, E, R, M>
+    pub fn control<NC>(self, control_builder: NC) -> Experiment<T, NC, E, R, M, I, N, G>
     where
         NC: Future<Output = T>,
     {
         Experiment {
             control_builder,
             name: self.name,
+            ordering: self.ordering,
             experimental_builder: self.experimental_builder,
             result_type: self.result_type,
             rollout_strategy: self.rollout_strategy,
             mismatch_handler: self.mismatch_handler,
+            instrumentation: self.instrumentation,
+            normalizer: self.normalizer,
+            ignore: self.ignore,
         }
     }
 
     /// Use the future given here as the experimental, or the new method for
-    /// calculating a value
-    pub fn experimental<NE>(self, experimental_builder: NE) -> Experiment<T, C, NE, R, M>
+    /// calculating a value. Unless a [`normalize`](Experiment::normalize) key
+    /// or a [`compare`](Experiment::compare) predicate has been supplied, the
+    /// experimental method must produce the same type as the control.
+    pub fn experimental<NE>(self, experimental_builder: NE) -> Experiment<T, C, NE, R, M, I, N, G>
     where
-        NE: Future<Output = T>,
+        NE: Future,
     {
         Experiment {
             experimental_builder,
             name: self.name,
+            ordering: self.ordering,
             result_type: self.result_type,
             control_builder: self.control_builder,
             rollout_strategy: self.rollout_strategy,
             mismatch_handler: self.mismatch_handler,
+            instrumentation: self.instrumentation,
+            normalizer: self.normalizer,
+            ignore: self.ignore,
         }
     }
 
     /// Use the given strategy for rolling out the new code
-    pub fn rollout_strategy<NR>(self, rollout_strategy: NR) -> Experiment<T, C, E, NR, M> {
+    pub fn rollout_strategy<NR>(self, rollout_strategy: NR) -> Experiment<T, C, E, NR, M, I, N, G> {
         Experiment {
             rollout_strategy,
             name: self.name,
+            ordering: self.ordering,
+            result_type: self.result_type,
+            control_builder: self.control_builder,
+            experimental_builder: self.experimental_builder,
+            mismatch_handler: self.mismatch_handler,
+            instrumentation: self.instrumentation,
+            normalizer: self.normalizer,
+            ignore: self.ignore,
+        }
+    }
+
+    /// Report experiment telemetry through the given [`Instrumentation`] instead
+    /// of the default [`MetricsTracing`]. Use [`NoInstrumentation`] to opt out
+    /// entirely, or provide your own to route events to OpenTelemetry, StatsD,
+    /// or a custom sink.
+    ///
+    /// [`NoInstrumentation`]: crate::instrumentation::NoInstrumentation
+    pub fn instrumentation<NI>(self, instrumentation: NI) -> Experiment<T, C, E, R, M, NI, N, G>
+    where
+        NI: Instrumentation,
+    {
+        Experiment {
+            instrumentation,
+            name: self.name,
+            ordering: self.ordering,
+            result_type: self.result_type,
+            control_builder: self.control_builder,
+            experimental_builder: self.experimental_builder,
+            rollout_strategy: self.rollout_strategy,
+            mismatch_handler: self.mismatch_handler,
+            normalizer: self.normalizer,
+            ignore: self.ignore,
+        }
+    }
+
+    /// Compare the control and experimental values by mapping each into a common
+    /// key rather than comparing them directly. This lets the two arms produce
+    /// different types — e.g. a legacy struct and its replacement DTO — during
+    /// an incremental migration, comparing only the fields that must agree.
+    ///
+    /// `run` still returns the control's value on a mismatch; only the
+    /// comparison is affected.
+    pub fn normalize<FC, FE, K>(
+        self,
+        control: FC,
+        experimental: FE,
+    ) -> Experiment<T, C, E, R, M, I, FnNormalizer<FC, FE>, G>
+    where
+        FC: Fn(&T) -> K,
+        FE: Fn(&<E as Future>::Output) -> K,
+        E: Future,
+        K: PartialEq,
+    {
+        Experiment {
+            normalizer: FnNormalizer {
+                control,
+                experimental,
+            },
+            name: self.name,
+            ordering: self.ordering,
             result_type: self.result_type,
             control_builder: self.control_builder,
             experimental_builder: self.experimental_builder,
+            rollout_strategy: self.rollout_strategy,
             mismatch_handler: self.mismatch_handler,
+            instrumentation: self.instrumentation,
+            ignore: self.ignore,
         }
     }
 
+    /// Override the equality test with a custom predicate, for values that are
+    /// semantically equal but not bit-equal — unordered collections, floats
+    /// within a tolerance, timestamps. Returns `true` when the two values should
+    /// be treated as matching.
+    pub fn compare<F>(self, compare: F) -> Experiment<T, C, E, R, M, I, FnComparison<F>, G>
+    where
+        E: Future,
+        F: Fn(&T, &<E as Future>::Output) -> bool,
+    {
+        Experiment {
+            normalizer: FnComparison(compare),
+            name: self.name,
+            ordering: self.ordering,
+            result_type: self.result_type,
+            control_builder: self.control_builder,
+            experimental_builder: self.experimental_builder,
+            rollout_strategy: self.rollout_strategy,
+            mismatch_handler: self.mismatch_handler,
+            instrumentation: self.instrumentation,
+            ignore: self.ignore,
+        }
+    }
+
+    /// Tolerate specific, known-acceptable divergences. When the predicate
+    /// returns `true` for a mismatch, the mismatch handler is not invoked and
+    /// the divergence is counted as `outcome = "ignored"` rather than
+    /// `"mismatch"`, so dashboards can separate tolerated differences from real
+    /// regressions. The experiment returns the control value, as it does for any
+    /// mismatch.
+    pub fn ignore<F>(self, ignore: F) -> Experiment<T, C, E, R, M, I, N, mismatch::FnIgnore<F>>
+    where
+        E: Future,
+        F: Fn(&Mismatch<T, <E as Future>::Output>) -> bool,
+    {
+        Experiment {
+            ignore: mismatch::FnIgnore(ignore),
+            name: self.name,
+            ordering: self.ordering,
+            result_type: self.result_type,
+            control_builder: self.control_builder,
+            experimental_builder: self.experimental_builder,
+            rollout_strategy: self.rollout_strategy,
+            mismatch_handler: self.mismatch_handler,
+            instrumentation: self.instrumentation,
+            normalizer: self.normalizer,
+        }
+    }
+
+    /// Choose the order in which the control and experimental arms are polled on
+    /// the compare path. Defaults to [`Ordering::ControlFirst`]; use
+    /// [`Ordering::Random`] so neither arm gets a systematic head start when
+    /// comparing latencies.
+    pub fn ordering(self, ordering: Ordering) -> Experiment<T, C, E, R, M, I, N, G> {
+        Experiment { ordering, ..self }
+    }
+
     /// Call this function when running the experiment results in a different
     /// value from the control and experimental methods. This can only happen
     /// when the rollout strategy returns
     /// `RolloutDecision::UseExperimentalAndCompare`.
-    pub fn on_mismatch<NM>(self, on_mismatch: NM) -> Experiment<T, C, E, R, mismatch::FnTrait<NM>>
+    pub fn on_mismatch<NM>(
+        self,
+        on_mismatch: NM,
+    ) -> Experiment<T, C, E, R, mismatch::FnTrait<NM>, I, N, G>
     where
-        NM: FnOnce(Mismatch<T>) -> T,
+        E: Future,
+        NM: FnOnce(Mismatch<T, <E as Future>::Output>) -> T,
     {
         Experiment {
             mismatch_handler: mismatch::FnTrait(on_mismatch),
             name: self.name,
+            ordering: self.ordering,
             rollout_strategy: self.rollout_strategy,
             result_type: self.result_type,
             control_builder: self.control_builder,
             experimental_builder: self.experimental_builder,
+            instrumentation: self.instrumentation,
+            normalizer: self.normalizer,
+            ignore: self.ignore,
         }
     }
 
     /// Run the experiment with the parameters provided
     pub async fn run(self) -> T
     where
-        T: PartialEq,
         R: RolloutStrategy,
-        M: MismatchHandler<T>,
+        I: Instrumentation,
         C: Future<Output = T>,
-        E: Future<Output = T>,
+        E: Future,
+        N: Normalizer<T, E::Output>,
+        G: Ignore<T, E::Output>,
+        M: MismatchHandler<T, E::Output, Output = T>,
     {
         let span = info_span!("Experiment::run", experiment_name = self.name);
-        counter!("thesis_experiment_run_total", 1, "name" => self.name);
+        self.instrumentation.on_run(self.name);
 
         async move {
             match self.rollout_strategy.rollout_decision() {
                 RolloutDecision::UseControl => {
-                    counter!("thesis_experiment_run_variant", 1, "name" => self.name, "kind" => "control");
+                    self.instrumentation.on_variant(self.name, "control");
+
+                    let (control, elapsed) = timed(span_control(self.control_builder)).await;
+                    report_timing(&self.instrumentation, self.name, "control", elapsed);
 
-                    span_control(self.control_builder).await
+                    control
                 },
                RolloutDecision::UseExperimentalAndCompare => {
-                    counter!("thesis_experiment_run_variant", 1, "name" => self.name, "kind" => "experimental_and_compare");
+                    self.instrumentation.on_variant(self.name, "experimental_and_compare");
 
-                    let (control, experimental) =
-                        tokio::join!(span_control(self.control_builder), span_experimental(self.experimental_builder));
+                    let ((control, control_elapsed), (experimental, experimental_elapsed)) =
+                        poll_pair(self.ordering, span_control(self.control_builder), span_experimental(self.experimental_builder)).await;
 
-                    if control != experimental {
-                        outcome_mismatch(self.name);
+                    report_timing(&self.instrumentation, self.name, "control", control_elapsed);
+                    report_timing(&self.instrumentation, self.name, "experimental", experimental_elapsed);
+                    report_speedup(&self.instrumentation, self.name, control_elapsed, experimental_elapsed);
 
+                    if !self.normalizer.matches(&control, &experimental) {
                         let mismatch = Mismatch {
                             control,
                             experimental,
                         };
 
+                        if self.ignore.ignore(&mismatch) {
+                            self.instrumentation.on_ignored(self.name);
+
+                            return mismatch.control;
+                        }
+
+                        self.instrumentation.on_mismatch(self.name);
+
                         return self.mismatch_handler.on_mismatch(mismatch);
                     }
 
@@ -159,97 +404,119 @@ impl<T, C, E, R, M> Experiment<T, C, E, R, M> {
     }
 }
 
-fn outcome_error<E>(name: &'static str, kind: &'static str, error: &E)
-where
-    E: Display,
-{
-    counter!("thesis_experiment_outcome", 1, "name" => name, "kind" => kind, "outcome" => "error");
-    tracing::error!(name, kind, %error, "thesis experiment error");
-}
-
-fn outcome_ok(name: &'static str, kind: &'static str) {
-    counter!("thesis_experiment_outcome", 1, "name" => name, "kind" => kind, "outcome" => "ok");
-}
-
-fn outcome_mismatch(name: &'static str) {
-    counter!("thesis_experiment_outcome", 1, "name" => name, "kind" => "experimental_and_compare", "outcome" => "mismatch");
-}
-
-fn outcome<T, E>(name: &'static str, kind: &'static str, result: &Result<T, E>)
+fn outcome<T, Err, I>(instrumentation: &I, name: &'static str, kind: &'static str, result: &Result<T, Err>)
 where
-    E: Display,
+    Err: Display,
+    I: Instrumentation,
 {
     match result {
         Ok(_) => {
-            outcome_ok(name, kind);
+            instrumentation.on_outcome(name, kind, true);
         }
         Err(e) => {
-            outcome_error(name, kind, e);
+            instrumentation.on_error(name, kind, e);
         }
     }
 }
 
-impl<T, Err, C, E, R, M> Experiment<Result<T, Err>, C, E, R, M> {
-    /// Run the experiment with the parameters provided
+impl<T, Err, C, E, R, M, I, N, G> Experiment<Result<T, Err>, C, E, R, M, I, N, G> {
+    /// Run the experiment with the parameters provided.
+    ///
+    /// Because the error arm only needs to be `Display` (not `PartialEq`), the
+    /// `Ok` payloads are always compared with `PartialEq`: routing the whole
+    /// `Result` through a [`Normalizer`] would force `Err: PartialEq` on every
+    /// caller. For that reason [`compare`](Experiment::compare) and
+    /// [`normalize`](Experiment::normalize) do **not** affect `run_result`. An
+    /// [`ignore`](Experiment::ignore) predicate still applies and can tolerate
+    /// known divergences; for a fully custom comparison, return a plain value
+    /// and use [`run`](Experiment::run) instead.
     pub async fn run_result(self) -> Result<T, Err>
     where
         T: PartialEq,
         R: RolloutStrategy,
-        M: MismatchHandler<Result<T, Err>>,
+        M: MismatchHandler<Result<T, Err>, Result<T, Err>, Output = Result<T, Err>>,
+        I: Instrumentation,
+        G: Ignore<Result<T, Err>, Result<T, Err>>,
         C: Future<Output = Result<T, Err>>,
         E: Future<Output = Result<T, Err>>,
         Err: Display,
     {
         let span = info_span!("Experiment::run", experiment_name = self.name);
-        counter!("thesis_experiment_run_total", 1, "name" => self.name);
+        self.instrumentation.on_run(self.name);
 
         async move {
             match self.rollout_strategy.rollout_decision() {
                 RolloutDecision::UseControl => {
-                    counter!("thesis_experiment_run_variant", 1, "name" => self.name, "kind" => "control");
+                    self.instrumentation.on_variant(self.name, "control");
 
-                    let result = span_control(self.control_builder).await;
-                    outcome(self.name, "control", &result);
+                    let (result, elapsed) = timed(span_control(self.control_builder)).await;
+                    report_timing(&self.instrumentation, self.name, "control", elapsed);
+                    outcome(&self.instrumentation, self.name, "control", &result);
 
                     result
                 },
                 RolloutDecision::UseExperimentalAndCompare => {
-                    counter!("thesis_experiment_run_variant", 1, "name" => self.name, "kind" => "experimental_and_compare");
+                    self.instrumentation.on_variant(self.name, "experimental_and_compare");
+
+                    let ((control, control_elapsed), (experimental, experimental_elapsed)) =
+                        poll_pair(self.ordering, span_control(self.control_builder), span_experimental(self.experimental_builder)).await;
 
-                    let (control, experimental) =
-                        tokio::join!(span_control(self.control_builder), span_experimental(self.experimental_builder));
+                        report_timing(&self.instrumentation, self.name, "control", control_elapsed);
+                        report_timing(&self.instrumentation, self.name, "experimental", experimental_elapsed);
+                        report_speedup(&self.instrumentation, self.name, control_elapsed, experimental_elapsed);
 
-                        outcome(self.name, "control", &control);
-                        outcome(self.name, "experimental", &experimental);
+                        outcome(&self.instrumentation, self.name, "control", &control);
+                        outcome(&self.instrumentation, self.name, "experimental", &experimental);
 
                         match (control, experimental) {
                             (Ok(control), Ok(experimental)) => {
                                 if control != experimental {
-                                    outcome_mismatch(self.name);
-
                                     let mismatch = Mismatch {
                                         control: Ok(control),
                                         experimental: Ok(experimental),
                                     };
 
+                                    if self.ignore.ignore(&mismatch) {
+                                        self.instrumentation.on_ignored(self.name);
+
+                                        return mismatch.control;
+                                    }
+
+                                    self.instrumentation.on_mismatch(self.name);
+
                                     return self.mismatch_handler.on_mismatch(mismatch);
                                 }
 
                                 Ok(control)
                             }
-                            (Ok(control), Err(_)) => {
-                                outcome_mismatch(self.name);
+                            (Ok(control), Err(experimental)) => {
+                                let mismatch = Mismatch {
+                                    control: Ok(control),
+                                    experimental: Err(experimental),
+                                };
+
+                                if self.ignore.ignore(&mismatch) {
+                                    self.instrumentation.on_ignored(self.name);
+                                } else {
+                                    self.instrumentation.on_mismatch(self.name);
+                                }
 
-                                Ok(control)
+                                mismatch.control
                             }
                             (Err(control), Ok(experimental)) => {
-                                    outcome_mismatch(self.name);
-
                                     let mismatch = Mismatch {
                                         control: Err(control),
                                         experimental: Ok(experimental),
                                     };
 
+                                    if self.ignore.ignore(&mismatch) {
+                                        self.instrumentation.on_ignored(self.name);
+
+                                        return mismatch.control;
+                                    }
+
+                                    self.instrumentation.on_mismatch(self.name);
+
                                     return self.mismatch_handler.on_mismatch(mismatch);
                             }
                             (Err(control), Err(_)) => {
@@ -384,6 +651,91 @@ mod tests {
         assert_eq!(seen, true);
     }
 
+    #[tokio::test]
+    async fn it_polls_sequentially_when_asked() {
+        use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+
+        // Sequential runs the arms one at a time (in a randomly chosen order),
+        // so neither arm should ever observe the other in flight.
+        let in_flight = AtomicUsize::new(0);
+
+        let arm = || async {
+            assert_eq!(
+                in_flight.fetch_add(1, AtomicOrdering::SeqCst),
+                0,
+                "arms should not overlap when polled sequentially"
+            );
+            in_flight.fetch_sub(1, AtomicOrdering::SeqCst);
+            1
+        };
+
+        let result = Experiment::new("test")
+            .control(arm())
+            .experimental(arm())
+            .rollout_strategy(Percent::new(100.0))
+            .ordering(Ordering::Sequential)
+            .run()
+            .await;
+
+        assert_eq!(result, 1);
+    }
+
+    #[tokio::test]
+    async fn it_honors_a_custom_comparison() {
+        // `0.1 + 0.2 != 0.3` under `PartialEq`, but they're equal within a
+        // tolerance, so a custom comparison should see no mismatch.
+        let result = Experiment::new("test")
+            .control(async { 0.3_f64 })
+            .experimental(async { 0.1_f64 + 0.2_f64 })
+            .compare(|control, experimental| (control - experimental).abs() < 1e-9)
+            .rollout_strategy(Percent::new(100.0))
+            .on_mismatch(|_| panic!("values were within tolerance, should not mismatch"))
+            .run()
+            .await;
+
+        assert_eq!(result, 0.3);
+    }
+
+    #[tokio::test]
+    async fn it_tolerates_ignored_mismatches() {
+        let result = Experiment::new("test")
+            .control(async { 1 })
+            .experimental(async { 2 })
+            .ignore(|mismatch| mismatch.control < mismatch.experimental)
+            .rollout_strategy(Percent::new(100.0))
+            .on_mismatch(|_| panic!("mismatch should have been ignored"))
+            .run()
+            .await;
+
+        assert_eq!(result, 1);
+    }
+
+    #[tokio::test]
+    async fn it_normalizes_differing_types_into_a_key() {
+        // The control returns a legacy tuple while the experimental returns a
+        // struct; they agree once normalized to the id they carry.
+        struct Dto {
+            id: i32,
+            _extra: &'static str,
+        }
+
+        let result = Experiment::new("test")
+            .control(async { (7, "legacy") })
+            .experimental(async {
+                Dto {
+                    id: 7,
+                    _extra: "new",
+                }
+            })
+            .normalize(|control: &(i32, &str)| control.0, |experimental: &Dto| experimental.id)
+            .rollout_strategy(Percent::new(100.0))
+            .on_mismatch(|_| panic!("ids matched, should not mismatch"))
+            .run()
+            .await;
+
+        assert_eq!(result, (7, "legacy"));
+    }
+
     #[tokio::test]
     async fn it_works_with_non_partialeq_errs() {
         #[derive(Debug)]
@@ -415,4 +767,67 @@ mod tests {
 
         assert_eq!(seen, true);
     }
+
+    #[tokio::test]
+    async fn it_reports_events_through_instrumentation() {
+        use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+        use std::sync::Arc;
+
+        #[derive(Default)]
+        struct Counts {
+            mismatches: AtomicUsize,
+            ignored: AtomicUsize,
+            timings: AtomicUsize,
+        }
+
+        #[derive(Clone)]
+        struct Counting(Arc<Counts>);
+
+        impl Instrumentation for Counting {
+            fn on_mismatch(&self, _name: &'static str) {
+                self.0.mismatches.fetch_add(1, AtomicOrdering::SeqCst);
+            }
+
+            fn on_ignored(&self, _name: &'static str) {
+                self.0.ignored.fetch_add(1, AtomicOrdering::SeqCst);
+            }
+
+            fn on_timing(&self, _name: &'static str, _kind: &'static str, _elapsed: Duration) {
+                self.0.timings.fetch_add(1, AtomicOrdering::SeqCst);
+            }
+        }
+
+        let counts = Arc::new(Counts::default());
+
+        // A compare run whose arms disagree should time both arms and report a
+        // single mismatch.
+        Experiment::new("test")
+            .control(async { 1 })
+            .experimental(async { 2 })
+            .instrumentation(Counting(Arc::clone(&counts)))
+            .rollout_strategy(Percent::new(100.0))
+            .on_mismatch(|m| m.control)
+            .run()
+            .await;
+
+        assert_eq!(counts.mismatches.load(AtomicOrdering::SeqCst), 1);
+        assert_eq!(counts.timings.load(AtomicOrdering::SeqCst), 2);
+        assert_eq!(counts.ignored.load(AtomicOrdering::SeqCst), 0);
+
+        // A disagreement tolerated by `ignore` is reported as ignored, not as a
+        // mismatch.
+        Experiment::new("test")
+            .control(async { 1 })
+            .experimental(async { 2 })
+            .instrumentation(Counting(Arc::clone(&counts)))
+            .ignore(|m| m.control < m.experimental)
+            .rollout_strategy(Percent::new(100.0))
+            .on_mismatch(|_| panic!("mismatch should have been ignored"))
+            .run()
+            .await;
+
+        assert_eq!(counts.mismatches.load(AtomicOrdering::SeqCst), 1);
+        assert_eq!(counts.ignored.load(AtomicOrdering::SeqCst), 1);
+        assert_eq!(counts.timings.load(AtomicOrdering::SeqCst), 4);
+    }
 }
@@ -0,0 +1,110 @@
+use std::fmt::Display;
+use std::time::Duration;
+
+use metrics::{counter, gauge, histogram};
+
+use crate::latency;
+
+/// A hook for reporting what an [`Experiment`](crate::Experiment) does as it
+/// runs.
+///
+/// By default an experiment uses [`MetricsTracing`], which emits the same
+/// `metrics` counters and `tracing` events thesis has always emitted. Provide
+/// your own implementation with
+/// [`Experiment::instrumentation`](crate::Experiment::instrumentation) to route
+/// telemetry into OpenTelemetry, StatsD, a test double, or anywhere else
+/// without pulling in the `metrics` stack.
+///
+/// Every method has a no-op default so implementors only need to override the
+/// events they care about.
+pub trait Instrumentation {
+    /// Called once each time an experiment is run, before the rollout decision
+    /// is made.
+    fn on_run(&self, _name: &'static str) {}
+
+    /// Called with the variant that the rollout strategy selected. `kind` is
+    /// either `"control"` or `"experimental_and_compare"`.
+    fn on_variant(&self, _name: &'static str, _kind: &'static str) {}
+
+    /// Called by [`Experiment::run_result`](crate::Experiment::run_result) when
+    /// a variant produced a result; `Experiment::run` does not call it. `kind`
+    /// identifies the arm
+    /// (`"control"` or `"experimental"`) and `ok` is `false` when the arm
+    /// resolved to an error.
+    fn on_outcome(&self, _name: &'static str, _kind: &'static str, _ok: bool) {}
+
+    /// Called when a variant resolved to an error. The default delegates to
+    /// [`Instrumentation::on_outcome`] with `ok = false`; override it to report
+    /// the error itself.
+    fn on_error(&self, name: &'static str, kind: &'static str, _error: &dyn Display) {
+        self.on_outcome(name, kind, false);
+    }
+
+    /// Called when the control and experimental values disagree and no `ignore`
+    /// predicate tolerated the mismatch.
+    fn on_mismatch(&self, _name: &'static str) {}
+
+    /// Called when a mismatch was tolerated by an `ignore` predicate rather than
+    /// reported.
+    fn on_ignored(&self, _name: &'static str) {}
+
+    /// Called with the wall-clock time a variant took to resolve. `kind`
+    /// identifies the arm (`"control"` or `"experimental"`).
+    fn on_timing(&self, _name: &'static str, _kind: &'static str, _elapsed: Duration) {}
+
+    /// Called on each compare run with the speedup ratio of the experimental
+    /// arm relative to the control, i.e. `control_latency / experimental_latency`
+    /// (values above `1.0` mean the experimental arm was faster). Not called
+    /// when the experimental arm's elapsed time is zero.
+    fn on_speedup(&self, _name: &'static str, _ratio: f64) {}
+}
+
+/// The default [`Instrumentation`], reproducing thesis' built-in behavior by
+/// emitting `thesis_experiment_*` counters via the `metrics` crate and logging
+/// errors through `tracing`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MetricsTracing;
+
+impl Instrumentation for MetricsTracing {
+    fn on_run(&self, name: &'static str) {
+        counter!("thesis_experiment_run_total", 1, "name" => name);
+    }
+
+    fn on_variant(&self, name: &'static str, kind: &'static str) {
+        counter!("thesis_experiment_run_variant", 1, "name" => name, "kind" => kind);
+    }
+
+    fn on_outcome(&self, name: &'static str, kind: &'static str, ok: bool) {
+        let outcome = if ok { "ok" } else { "error" };
+        counter!("thesis_experiment_outcome", 1, "name" => name, "kind" => kind, "outcome" => outcome);
+    }
+
+    fn on_error(&self, name: &'static str, kind: &'static str, error: &dyn Display) {
+        counter!("thesis_experiment_outcome", 1, "name" => name, "kind" => kind, "outcome" => "error");
+        tracing::error!(name, kind, %error, "thesis experiment error");
+    }
+
+    fn on_mismatch(&self, name: &'static str) {
+        counter!("thesis_experiment_outcome", 1, "name" => name, "kind" => "experimental_and_compare", "outcome" => "mismatch");
+    }
+
+    fn on_ignored(&self, name: &'static str) {
+        counter!("thesis_experiment_outcome", 1, "name" => name, "kind" => "experimental_and_compare", "outcome" => "ignored");
+    }
+
+    fn on_timing(&self, name: &'static str, kind: &'static str, elapsed: Duration) {
+        latency::record(name, kind, elapsed);
+        histogram!("thesis_experiment_latency", elapsed.as_secs_f64(), "name" => name, "kind" => kind);
+    }
+
+    fn on_speedup(&self, name: &'static str, ratio: f64) {
+        gauge!("thesis_experiment_speedup", ratio, "name" => name);
+    }
+}
+
+/// An [`Instrumentation`] that does nothing, for users who want to opt out of
+/// telemetry entirely.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NoInstrumentation;
+
+impl Instrumentation for NoInstrumentation {}
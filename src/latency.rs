@@ -0,0 +1,106 @@
+//! Per-experiment latency tracking.
+//!
+//! Comparing return values tells you whether the experimental method is
+//! *correct*; comparing latencies tells you whether it's *worth it*. Each time
+//! an experiment runs, thesis times the control and experimental futures and
+//! hands the elapsed durations to its [`Instrumentation`]. The default
+//! [`MetricsTracing`] feeds them into an HDR histogram per variant, keyed by
+//! experiment name; other instrumentations record nothing here unless they
+//! call into this module themselves. Query the accumulated distribution with
+//! [`percentiles`].
+//!
+//! [`Instrumentation`]: crate::Instrumentation
+//! [`MetricsTracing`]: crate::MetricsTracing
+
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+use hdrhistogram::Histogram;
+
+/// The `p50`/`p90`/`p99` of a variant's recorded latencies.
+#[derive(Clone, Copy, Debug)]
+pub struct Percentiles {
+    /// Median latency.
+    pub p50: Duration,
+    /// 90th percentile latency.
+    pub p90: Duration,
+    /// 99th percentile latency.
+    pub p99: Duration,
+}
+
+type Registry = HashMap<(&'static str, &'static str), Histogram<u64>>;
+
+fn registry() -> &'static Mutex<Registry> {
+    static REGISTRY: OnceLock<Mutex<Registry>> = OnceLock::new();
+    REGISTRY.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Record a latency sample for the `variant` (`"control"` or `"experimental"`)
+/// of the named experiment.
+pub(crate) fn record(name: &'static str, variant: &'static str, elapsed: Duration) {
+    // A `Duration` is never negative; clamp it to a representable microsecond
+    // count so an absurdly large duration can't panic the recording path.
+    let micros = u64::try_from(elapsed.as_micros()).unwrap_or(u64::MAX);
+
+    let mut registry = registry().lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let histogram = registry
+        .entry((name, variant))
+        // Three significant figures matches the precision tower-balance uses and
+        // keeps each histogram small while covering a wide dynamic range.
+        .or_insert_with(|| Histogram::new(3).expect("valid significant figures"));
+
+    // `record` only fails when a value exceeds the histogram's auto-resized
+    // bounds, which can't happen for a plain `Histogram::new`.
+    let _ = histogram.record(micros);
+}
+
+/// Fetch the `p50`/`p90`/`p99` latencies recorded so far for the `variant`
+/// (`"control"` or `"experimental"`) of the named experiment, or `None` if no
+/// samples have been recorded.
+///
+/// The histograms live in a process-global registry keyed by
+/// `(name, variant)`, so the returned percentiles aggregate every sample ever
+/// recorded under that name across the whole process, from the first run until
+/// now — there is no windowing or reset. Two experiments sharing a name share a
+/// distribution, so give distinct experiments distinct names if you want to
+/// read their latencies apart.
+pub fn percentiles(name: &'static str, variant: &'static str) -> Option<Percentiles> {
+    let registry = registry().lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let histogram = registry.get(&(name, variant))?;
+
+    if histogram.is_empty() {
+        return None;
+    }
+
+    Some(Percentiles {
+        p50: Duration::from_micros(histogram.value_at_quantile(0.50)),
+        p90: Duration::from_micros(histogram.value_at_quantile(0.90)),
+        p99: Duration::from_micros(histogram.value_at_quantile(0.99)),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_reports_ordered_percentiles_after_recording() {
+        // Use a name unique to this test: the registry is process-global, so a
+        // shared name would blend samples from other tests into the result.
+        let name = "latency::it_reports_ordered_percentiles_after_recording";
+
+        assert!(percentiles(name, "control").is_none());
+
+        for millis in 1..=100 {
+            record(name, "control", Duration::from_millis(millis));
+        }
+
+        let percentiles = percentiles(name, "control").expect("samples were recorded");
+        assert!(
+            percentiles.p50 <= percentiles.p90 && percentiles.p90 <= percentiles.p99,
+            "percentiles should be non-decreasing, got {:?}",
+            percentiles
+        );
+    }
+}
@@ -42,7 +42,17 @@
 //! ```
 
 pub mod experiment;
+pub mod instrumentation;
+pub mod latency;
+pub mod mismatch;
+pub mod normalize;
+pub mod ordering;
 pub mod rollout;
+#[cfg(feature = "tower")]
+pub mod tower;
 
 pub use experiment::Experiment;
+pub use instrumentation::{Instrumentation, MetricsTracing, NoInstrumentation};
+pub use mismatch::{Mismatch, MismatchHandler};
+pub use ordering::Ordering;
 pub use rollout::{RolloutDecision, RolloutStrategy};
@@ -0,0 +1,81 @@
+//! Resolving disagreements between an experiment's control and experimental
+//! methods.
+
+/// The differing values produced by an experiment's control and experimental
+/// methods. The two arms may produce different types once a comparison key has
+/// been supplied with [`Experiment::normalize`](crate::Experiment::normalize)
+/// or a predicate with [`Experiment::compare`](crate::Experiment::compare).
+pub struct Mismatch<C, E> {
+    /// The value produced by the control method.
+    pub control: C,
+    /// The value produced by the experimental method.
+    pub experimental: E,
+}
+
+/// Decides which value an experiment returns when its control and experimental
+/// methods disagree.
+pub trait MismatchHandler<C, E> {
+    /// The value produced when resolving a mismatch.
+    type Output;
+
+    /// Resolve the mismatch into the value the experiment should return.
+    fn on_mismatch(self, mismatch: Mismatch<C, E>) -> Self::Output;
+}
+
+/// The default [`MismatchHandler`], which discards the experimental value and
+/// keeps the control's.
+pub struct AlwaysControl;
+
+impl<C, E> MismatchHandler<C, E> for AlwaysControl {
+    type Output = C;
+
+    fn on_mismatch(self, mismatch: Mismatch<C, E>) -> C {
+        mismatch.control
+    }
+}
+
+/// A [`MismatchHandler`] backed by a closure. Created by
+/// [`Experiment::on_mismatch`](crate::Experiment::on_mismatch).
+pub struct FnTrait<F>(pub F);
+
+impl<C, E, O, F> MismatchHandler<C, E> for FnTrait<F>
+where
+    F: FnOnce(Mismatch<C, E>) -> O,
+{
+    type Output = O;
+
+    fn on_mismatch(self, mismatch: Mismatch<C, E>) -> O {
+        (self.0)(mismatch)
+    }
+}
+
+/// Decides whether a mismatch is an acceptable, known divergence that should be
+/// tolerated rather than reported. A tolerated mismatch is counted as
+/// `outcome = "ignored"` instead of `"mismatch"` and does not invoke the
+/// [`MismatchHandler`].
+pub trait Ignore<C, E> {
+    /// Returns `true` when the mismatch should be tolerated.
+    fn ignore(&self, mismatch: &Mismatch<C, E>) -> bool;
+}
+
+/// The default [`Ignore`], which never tolerates a mismatch.
+pub struct NeverIgnore;
+
+impl<C, E> Ignore<C, E> for NeverIgnore {
+    fn ignore(&self, _mismatch: &Mismatch<C, E>) -> bool {
+        false
+    }
+}
+
+/// An [`Ignore`] backed by a closure. Created by
+/// [`Experiment::ignore`](crate::Experiment::ignore).
+pub struct FnIgnore<F>(pub F);
+
+impl<C, E, F> Ignore<C, E> for FnIgnore<F>
+where
+    F: Fn(&Mismatch<C, E>) -> bool,
+{
+    fn ignore(&self, mismatch: &Mismatch<C, E>) -> bool {
+        (self.0)(mismatch)
+    }
+}
@@ -0,0 +1,60 @@
+//! Normalizing control and experimental values into a common comparison key.
+//!
+//! By default an experiment requires both arms to produce the same
+//! `PartialEq` type and compares them directly ([`Equality`]). When a refactor
+//! changes the shape of a value — a legacy struct becoming a new DTO, say —
+//! [`Experiment::normalize`](crate::Experiment::normalize) lets each arm map
+//! into a shared key `K` so the two can be compared without a lossy cast.
+
+/// Compares a control value of type `C` against an experimental value of type
+/// `E`, reporting whether they are considered equal.
+pub trait Normalizer<C, E> {
+    /// Returns `true` when the control and experimental values match.
+    fn matches(&self, control: &C, experimental: &E) -> bool;
+}
+
+/// The default [`Normalizer`], comparing two values of the same type with
+/// `PartialEq`.
+pub struct Equality;
+
+impl<T> Normalizer<T, T> for Equality
+where
+    T: PartialEq,
+{
+    fn matches(&self, control: &T, experimental: &T) -> bool {
+        control == experimental
+    }
+}
+
+/// A [`Normalizer`] that maps each arm into a common key with a closure before
+/// comparing. Created by [`Experiment::normalize`](crate::Experiment::normalize).
+pub struct FnNormalizer<FC, FE> {
+    pub(crate) control: FC,
+    pub(crate) experimental: FE,
+}
+
+impl<C, E, K, FC, FE> Normalizer<C, E> for FnNormalizer<FC, FE>
+where
+    FC: Fn(&C) -> K,
+    FE: Fn(&E) -> K,
+    K: PartialEq,
+{
+    fn matches(&self, control: &C, experimental: &E) -> bool {
+        (self.control)(control) == (self.experimental)(experimental)
+    }
+}
+
+/// A [`Normalizer`] backed by a closure that compares the two values directly,
+/// for equality tests that `PartialEq` can't express (unordered collections,
+/// floats within a tolerance, timestamps). Created by
+/// [`Experiment::compare`](crate::Experiment::compare).
+pub struct FnComparison<F>(pub(crate) F);
+
+impl<C, E, F> Normalizer<C, E> for FnComparison<F>
+where
+    F: Fn(&C, &E) -> bool,
+{
+    fn matches(&self, control: &C, experimental: &E) -> bool {
+        (self.0)(control, experimental)
+    }
+}
@@ -0,0 +1,30 @@
+//! Controlling the order in which an experiment's control and experimental
+//! methods are polled.
+//!
+//! On the compare path the two arms run together, and the arm that is polled
+//! first gets to warm any shared caches or connection pools before the other
+//! runs, giving it a systematic head start that biases latency measurements.
+//! [`Ordering`] chooses how to poll the arms so neither is consistently
+//! favored.
+
+/// How the control and experimental arms are polled on the compare path.
+///
+/// The default is [`Ordering::ControlFirst`], preserving thesis' original
+/// behavior of polling the control first.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Ordering {
+    /// Poll both arms concurrently, randomly choosing which is polled first on
+    /// each run so neither gets a systematic head start.
+    Random,
+
+    /// Poll both arms concurrently, polling the control first.
+    ControlFirst,
+
+    /// Poll both arms concurrently, polling the experimental first.
+    ExperimentalFirst,
+
+    /// Await the arms one at a time rather than concurrently, for cases where
+    /// true concurrency distorts the comparison. Which arm runs first is chosen
+    /// randomly on each run so neither gets a systematic head start.
+    Sequential,
+}
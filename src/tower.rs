@@ -0,0 +1,228 @@
+//! A [`tower`] integration that turns an experiment into middleware.
+//!
+//! Rather than hand-wiring `.control(...)`/`.experimental(...)` at each call
+//! site, wrap a whole endpoint or client stack in an [`ExperimentLayer`]. The
+//! layer pairs the existing ("control") service with a candidate service and a
+//! [`RolloutStrategy`]; on the compare path it drives both with a clone of the
+//! request, compares their responses through [`Experiment::run_result`], and
+//! returns the control's response while reporting any mismatch.
+//!
+//! Requires the `tower` feature.
+
+use std::fmt::Display;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use ::tower::{Layer, Service};
+
+use crate::rollout::RolloutStrategy;
+use crate::Experiment;
+
+/// A [`Layer`] that A/Bs a candidate service against the service it wraps.
+///
+/// The wrapped service is treated as the control; `candidate` is the new
+/// implementation under test. `rollout_strategy` decides, per request, whether
+/// to serve the control alone or to run both and compare.
+pub struct ExperimentLayer<Cand, R> {
+    candidate: Cand,
+    rollout_strategy: R,
+    name: &'static str,
+}
+
+impl<Cand, R> ExperimentLayer<Cand, R> {
+    /// Create a layer that experiments `candidate` against the service it wraps,
+    /// using `rollout_strategy` to decide when to compare.
+    pub fn new(name: &'static str, candidate: Cand, rollout_strategy: R) -> Self {
+        Self {
+            candidate,
+            rollout_strategy,
+            name,
+        }
+    }
+}
+
+impl<S, Cand, R> Layer<S> for ExperimentLayer<Cand, R>
+where
+    Cand: Clone,
+    R: Clone,
+{
+    type Service = ExperimentService<S, Cand, R>;
+
+    fn layer(&self, control: S) -> Self::Service {
+        ExperimentService {
+            control,
+            candidate: self.candidate.clone(),
+            rollout_strategy: self.rollout_strategy.clone(),
+            name: self.name,
+            control_ready: false,
+            candidate_ready: false,
+        }
+    }
+}
+
+/// The [`Service`] produced by [`ExperimentLayer`]. See the module docs.
+pub struct ExperimentService<Ctl, Cand, R> {
+    control: Ctl,
+    candidate: Cand,
+    rollout_strategy: R,
+    name: &'static str,
+    // `poll_ready` can be called repeatedly before a single `call`, so latch
+    // each arm once it reports ready and stop re-polling it. Re-polling a
+    // service that is already ready can double-reserve its capacity (e.g. a
+    // `Buffer`/`Semaphore` permit), leaking a reservation on every extra poll.
+    control_ready: bool,
+    candidate_ready: bool,
+}
+
+impl<Ctl, Cand, R, Request> Service<Request> for ExperimentService<Ctl, Cand, R>
+where
+    Request: Clone,
+    Ctl: Service<Request> + Clone + Send + 'static,
+    Ctl::Future: Send + 'static,
+    Ctl::Response: PartialEq + Send + 'static,
+    Ctl::Error: Display + Send + 'static,
+    Cand: Service<Request, Response = Ctl::Response, Error = Ctl::Error> + Clone + Send + 'static,
+    Cand::Future: Send + 'static,
+    R: RolloutStrategy + Clone + Send + 'static,
+{
+    type Response = Ctl::Response;
+    type Error = Ctl::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        // Both inner services must be ready before we can serve a request. Each
+        // arm is polled only until it reports ready, then latched, so repeated
+        // `poll_ready` calls don't reserve its capacity more than once.
+        if !self.control_ready {
+            match self.control.poll_ready(cx) {
+                Poll::Ready(Ok(())) => self.control_ready = true,
+                other => return other,
+            }
+        }
+
+        if !self.candidate_ready {
+            match self.candidate.poll_ready(cx) {
+                Poll::Ready(Ok(())) => self.candidate_ready = true,
+                other => return other,
+            }
+        }
+
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        // Consume the readiness latched by `poll_ready`; the next call must
+        // drive the freshly cloned services back to ready.
+        self.control_ready = false;
+        self.candidate_ready = false;
+
+        let decision = self.rollout_strategy.rollout_decision();
+
+        // Take the services that `poll_ready` made ready, leaving freshly cloned
+        // copies behind to be driven to readiness before the next call.
+        let mut control = {
+            let clone = self.control.clone();
+            std::mem::replace(&mut self.control, clone)
+        };
+        let mut candidate = {
+            let clone = self.candidate.clone();
+            std::mem::replace(&mut self.candidate, clone)
+        };
+
+        let name = self.name;
+        let control_req = req.clone();
+
+        Box::pin(async move {
+            Experiment::new(name)
+                .control(control.call(control_req))
+                .experimental(candidate.call(req))
+                .rollout_strategy(decision)
+                .run_result()
+                .await
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use ::tower::{service_fn, ServiceExt};
+
+    use super::*;
+    use crate::rollout::RolloutDecision;
+
+    /// Build a control and a candidate service that return different responses
+    /// for the same request, each counting how many of its response futures
+    /// were run.
+    fn diverging_services() -> (
+        impl Service<u32, Response = u32, Error = Infallible> + Clone,
+        impl Service<u32, Response = u32, Error = Infallible> + Clone,
+        Arc<AtomicUsize>,
+        Arc<AtomicUsize>,
+    ) {
+        let control_calls = Arc::new(AtomicUsize::new(0));
+        let candidate_calls = Arc::new(AtomicUsize::new(0));
+
+        let control = {
+            let calls = Arc::clone(&control_calls);
+            service_fn(move |req: u32| {
+                let calls = Arc::clone(&calls);
+                async move {
+                    calls.fetch_add(1, Ordering::SeqCst);
+                    Ok::<_, Infallible>(req)
+                }
+            })
+        };
+        let candidate = {
+            let calls = Arc::clone(&candidate_calls);
+            service_fn(move |req: u32| {
+                let calls = Arc::clone(&calls);
+                async move {
+                    calls.fetch_add(1, Ordering::SeqCst);
+                    // Deliberately disagree with the control.
+                    Ok::<_, Infallible>(req + 100)
+                }
+            })
+        };
+
+        (control, candidate, control_calls, candidate_calls)
+    }
+
+    #[tokio::test]
+    async fn it_serves_the_control_alone_on_the_control_path() {
+        let (control, candidate, control_calls, candidate_calls) = diverging_services();
+
+        let service = ExperimentLayer::new("tower test", candidate, RolloutDecision::UseControl)
+            .layer(control);
+
+        let response = service.oneshot(7).await.unwrap();
+
+        assert_eq!(response, 7);
+        assert_eq!(control_calls.load(Ordering::SeqCst), 1);
+        assert_eq!(candidate_calls.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn it_compares_both_and_returns_the_control_on_the_compare_path() {
+        let (control, candidate, control_calls, candidate_calls) = diverging_services();
+
+        let service = ExperimentLayer::new(
+            "tower test",
+            candidate,
+            RolloutDecision::UseExperimentalAndCompare,
+        )
+        .layer(control);
+
+        // Both arms run, the responses disagree, and the mismatch is handled by
+        // returning the control's response.
+        let response = service.oneshot(7).await.unwrap();
+
+        assert_eq!(response, 7);
+        assert_eq!(control_calls.load(Ordering::SeqCst), 1);
+        assert_eq!(candidate_calls.load(Ordering::SeqCst), 1);
+    }
+}